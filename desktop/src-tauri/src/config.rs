@@ -0,0 +1,63 @@
+//! Persistent backend configuration, stored as `voxgrep.toml` in the app config
+//! directory. Lets users point the desktop app at a differently-configured or
+//! remote backend instead of relying on the hardcoded launch heuristics.
+
+use std::path::Path;
+
+/// Name of the config file within the app config directory.
+const CONFIG_FILE: &str = "voxgrep.toml";
+
+/// Backend launch settings persisted across runs.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Explicit interpreter path; takes precedence over auto-resolution.
+    pub python_path: Option<String>,
+    /// Python module exposing the server entry point.
+    pub module: String,
+    /// Host the backend binds to.
+    pub host: String,
+    /// Port the backend listens on.
+    pub port: u16,
+    /// When false the app connects to an already-running backend instead of
+    /// spawning one.
+    pub auto_start: bool,
+    /// Log line the backend prints once it is accepting connections; its
+    /// appearance flips the readiness gate.
+    pub ready_marker: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            python_path: None,
+            module: "voxgrep.server.app".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 8000,
+            auto_start: true,
+            ready_marker: "VOXGREP_READY".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config from `config_dir`, falling back to defaults when the
+    /// file is absent or unreadable.
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(CONFIG_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Persist the config to `config_dir`, creating it if necessary.
+    pub fn save(&self, config_dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(config_dir)
+            .map_err(|e| format!("failed to create config dir: {e}"))?;
+        let contents =
+            toml::to_string_pretty(self).map_err(|e| format!("failed to serialize config: {e}"))?;
+        std::fs::write(config_dir.join(CONFIG_FILE), contents)
+            .map_err(|e| format!("failed to write config: {e}"))
+    }
+}
@@ -1,12 +1,373 @@
-use std::process::{Child, Command, Stdio};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager, Runtime};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+mod config;
+use config::Config;
+
+/// How often the supervisor reaps the child with `try_wait`.
+const SUPERVISOR_POLL: Duration = Duration::from_secs(1);
+/// Upper bound for the restart backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Consecutive crash-restarts tolerated before giving up.
+const MAX_RESTARTS: u32 = 5;
+/// Uptime after which the backend is considered healthy and the backoff resets.
+const HEALTHY_UPTIME: Duration = Duration::from_secs(60);
+/// How long to wait for the backend to come up before declaring a timeout.
+const READINESS_DEADLINE: Duration = Duration::from_secs(30);
+/// Grace period the backend gets to exit after SIGTERM before it's SIGKILLed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 struct AppState {
     python_process: Arc<Mutex<Option<Child>>>,
+    /// Set before a deliberate `kill()` so the supervisor doesn't mistake a
+    /// shutdown for a crash.
+    manually_killed: Arc<AtomicBool>,
+    /// Flipped once the backend reports itself ready; gates [`wait_for_backend`].
+    backend_ready: Arc<AtomicBool>,
+    /// Current backend settings, loaded from `voxgrep.toml` during setup.
+    config: Arc<Mutex<Config>>,
+}
+
+/// Payload emitted to the webview for each line of backend output.
+#[derive(Clone, serde::Serialize)]
+struct BackendLog {
+    stream: &'static str,
+    line: String,
+}
+
+/// Payload emitted once the backend is accepting connections.
+#[derive(Clone, serde::Serialize)]
+struct BackendReady {
+    host: String,
+    port: u16,
+}
+
+/// Drain `reader` line-by-line, emitting each line as a `backend-log` event.
+///
+/// Splits on `\n` and strips a trailing `\r` so Windows CRLF output isn't
+/// mangled. `read_until` yields a final newline-less line as `Ok(n > 0)`, so it
+/// is emitted by the loop like any other; EOF only ever arrives as a separate
+/// `Ok(0)`. When the readiness marker appears it flips `ready` and emits
+/// `backend-ready` exactly once.
+fn pump_logs<R, S>(
+    app: AppHandle<R>,
+    reader: S,
+    stream: &'static str,
+    ready: Arc<AtomicBool>,
+    marker: String,
+    host: String,
+    port: u16,
+) where
+    R: Runtime,
+    S: Read,
+{
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {
+                if buf.last() == Some(&b'\n') {
+                    buf.pop();
+                    if buf.last() == Some(&b'\r') {
+                        buf.pop();
+                    }
+                }
+                let line = String::from_utf8_lossy(&buf).into_owned();
+                if line.contains(&marker) && !ready.swap(true, Ordering::SeqCst) {
+                    let _ = app.emit(
+                        "backend-ready",
+                        BackendReady { host: host.clone(), port },
+                    );
+                }
+                let _ = app.emit("backend-log", BackendLog { stream, line });
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+/// Move the child's stdout/stderr into background tasks that forward every line
+/// to the webview, so the OS pipe buffers never fill and deadlock the backend.
+fn spawn_log_pumps<R: Runtime>(
+    app: &AppHandle<R>,
+    stdout: Option<ChildStdout>,
+    stderr: Option<ChildStderr>,
+    ready: &Arc<AtomicBool>,
+    marker: &str,
+    host: &str,
+    port: u16,
+) {
+    if let Some(stdout) = stdout {
+        let (app, ready, marker, host) =
+            (app.clone(), ready.clone(), marker.to_string(), host.to_string());
+        tauri::async_runtime::spawn_blocking(move || {
+            pump_logs(app, stdout, "stdout", ready, marker, host, port)
+        });
+    }
+    if let Some(stderr) = stderr {
+        let (app, ready, marker, host) =
+            (app.clone(), ready.clone(), marker.to_string(), host.to_string());
+        tauri::async_runtime::spawn_blocking(move || {
+            pump_logs(app, stderr, "stderr", ready, marker, host, port)
+        });
+    }
+}
+
+/// Emit `backend-timeout` if the backend hasn't reported ready by the deadline.
+fn spawn_readiness_watch<R: Runtime>(app: AppHandle<R>, ready: Arc<AtomicBool>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let deadline = Instant::now() + READINESS_DEADLINE;
+        while Instant::now() < deadline {
+            if ready.load(Ordering::SeqCst) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        if !ready.load(Ordering::SeqCst) {
+            let _ = app.emit("backend-timeout", ());
+        }
+    });
+}
+
+/// Block until the backend reports ready, or until the readiness deadline.
+///
+/// Returns `Ok(true)` once the backend is up so a splash screen can `await`
+/// real readiness instead of guessing, and `Ok(false)` on timeout.
+#[tauri::command]
+async fn wait_for_backend(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let ready = state.backend_ready.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let deadline = Instant::now() + READINESS_DEADLINE;
+        while Instant::now() < deadline {
+            if ready.load(Ordering::SeqCst) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        ready.load(Ordering::SeqCst)
+    })
+    .await
+    .map_err(|e| format!("readiness wait failed: {e}"))
+}
+
+/// Payload emitted when the supervisor respawns a crashed backend.
+#[derive(Clone, serde::Serialize)]
+struct BackendRestarted {
+    attempt: u32,
+}
+
+/// Payload emitted when the supervisor gives up after too many crashes.
+#[derive(Clone, serde::Serialize)]
+struct BackendFailed {
+    reason: String,
+}
+
+/// Watch the child in the background and respawn it when it exits unexpectedly.
+///
+/// Modeled on tauri-cli's `DevChild`: a shared handle plus a `manually_killed`
+/// flag distinguish deliberate shutdowns from crashes. On an unexpected exit
+/// the supervisor re-spawns with capped exponential backoff (1s, 2s, 4s … up to
+/// [`MAX_BACKOFF`]) and emits `backend-restarted`; after [`MAX_RESTARTS`]
+/// consecutive failures it emits `backend-failed` and stops.
+fn spawn_supervisor<R: Runtime>(
+    app: AppHandle<R>,
+    process: Arc<Mutex<Option<Child>>>,
+    manually_killed: Arc<AtomicBool>,
+    ready: Arc<AtomicBool>,
+    config: Arc<Mutex<Config>>,
+) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut restarts: u32 = 0;
+        let mut backoff = Duration::from_secs(1);
+        let mut last_start = Instant::now();
+
+        loop {
+            std::thread::sleep(SUPERVISOR_POLL);
+            if manually_killed.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let status = {
+                let mut lock = process.lock().unwrap();
+                match lock.as_mut() {
+                    Some(child) => child.try_wait().ok().flatten(),
+                    None => None,
+                }
+            };
+
+            let Some(status) = status else { continue };
+
+            // A kill racing with this poll is a clean shutdown, not a crash.
+            if manually_killed.load(Ordering::SeqCst) {
+                break;
+            }
+
+            // A backend that exits cleanly (status 0) is not restarted,
+            // however long it ran.
+            if status.success() {
+                eprintln!("Backend exited cleanly; supervisor stopping.");
+                break;
+            }
+
+            // Reset the backoff only if the *last start attempt* stayed up long
+            // enough. Keyed off `last_start`, which is advanced on every
+            // (re)spawn attempt below — including failed ones — so a backend
+            // that crashes into an unresolvable interpreter can't keep resetting
+            // the ceiling off a stale timestamp and retry forever.
+            if last_start.elapsed() >= HEALTHY_UPTIME {
+                restarts = 0;
+                backoff = Duration::from_secs(1);
+            }
+
+            // Give up *before* respawning once the restart budget is spent, so
+            // we never emit a `backend-restarted` for an attempt we're about to
+            // declare failed, and never leave a fresh child unsupervised.
+            if restarts >= MAX_RESTARTS {
+                let reason = format!("backend crashed {} times; giving up", restarts + 1);
+                let _ = app.emit("backend-failed", BackendFailed { reason });
+                break;
+            }
+
+            eprintln!("Backend exited unexpectedly ({status}); restarting in {backoff:?}.");
+
+            std::thread::sleep(backoff);
+
+            // A window-close that landed during the backoff sleep means
+            // shutdown was requested; don't spawn a fresh backend after the
+            // graceful stop. (`restart_backend` does not set this flag; a child
+            // it swapped in meanwhile is caught by the live-slot check below.)
+            if manually_killed.load(Ordering::SeqCst) {
+                break;
+            }
+
+            restarts += 1;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+
+            // The fresh process hasn't come up yet; re-arm the readiness gate.
+            ready.store(false, Ordering::SeqCst);
+            let current = config.lock().unwrap().clone();
+            last_start = Instant::now();
+            match start_backend(&app, &ready, &current) {
+                Ok(mut child) => {
+                    let mut lock = process.lock().unwrap();
+                    // Don't clobber a live child that `restart_backend` may have
+                    // swapped into the slot while we were spawning: dropping a
+                    // `Child` doesn't kill the process, so that would leak a
+                    // backend still holding the port.
+                    let slot_live = lock
+                        .as_mut()
+                        .map(|c| matches!(c.try_wait(), Ok(None)))
+                        .unwrap_or(false);
+                    if manually_killed.load(Ordering::SeqCst) {
+                        drop(lock);
+                        terminate_child(&mut child);
+                        break;
+                    }
+                    if slot_live {
+                        drop(lock);
+                        terminate_child(&mut child);
+                        continue;
+                    }
+                    *lock = Some(child);
+                    drop(lock);
+                    let _ = app.emit("backend-restarted", BackendRestarted { attempt: restarts });
+                }
+                Err(e) => {
+                    // Leave the dead child in the slot so the next poll
+                    // re-detects the exit and retries until the ceiling trips.
+                    eprintln!("Failed to respawn backend: {e}");
+                }
+            }
+        }
+    });
+}
+
+/// Structured error naming every location searched while resolving the backend
+/// interpreter, so the UI can show an actionable message instead of a silent
+/// failure.
+#[derive(Clone, serde::Serialize)]
+struct InterpreterError {
+    searched: Vec<String>,
+}
+
+impl std::fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "could not locate a Python interpreter; searched: {}",
+            self.searched.join(", ")
+        )
+    }
+}
+
+/// Resolve how to launch the backend.
+///
+/// Resolution order: an explicit path (from `config.python_path` or the
+/// `VOXGREP_PYTHON` env var), then a Poetry environment (`poetry run python`)
+/// located via `which`, then a project-local `.venv`, then
+/// `which("python3")`/`which("python")` on the `PATH`. Every location that was
+/// tried is recorded so failures name what was searched.
+fn resolve_backend_command(
+    project_root: &Path,
+    config: &Config,
+) -> Result<(PathBuf, Vec<String>), InterpreterError> {
+    let module = vec!["-m".to_string(), config.module.clone()];
+    let mut searched = Vec::new();
+
+    // 1. Explicit override from config, then from the environment.
+    let explicit = config
+        .python_path
+        .clone()
+        .map(|p| ("config.python_path", p))
+        .or_else(|| std::env::var("VOXGREP_PYTHON").ok().map(|p| ("VOXGREP_PYTHON", p)));
+    if let Some((source, path)) = explicit {
+        searched.push(format!("{source}={path}"));
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Ok((path, module));
+        }
+    }
+
+    // 2. Poetry-managed project: let poetry pick the virtualenv interpreter.
+    searched.push("which(\"poetry\")".to_string());
+    if let Ok(poetry) = which::which("poetry") {
+        let mut args = vec!["run".to_string(), "python".to_string()];
+        args.extend(module);
+        return Ok((poetry, args));
+    }
+
+    // 3. Project-local virtualenv.
+    for rel in [".venv/bin/python", ".venv/Scripts/python.exe"] {
+        let candidate = project_root.join(rel);
+        searched.push(candidate.display().to_string());
+        if candidate.is_file() {
+            return Ok((candidate, module));
+        }
+    }
+
+    // 4. Interpreter on the PATH.
+    for name in ["python3", "python"] {
+        searched.push(format!("which(\"{name}\")"));
+        if let Ok(path) = which::which(name) {
+            return Ok((path, module));
+        }
+    }
+
+    Err(InterpreterError { searched })
 }
 
-fn start_backend<R: Runtime>(_app: &AppHandle<R>) -> Result<Child, String> {
+fn start_backend<R: Runtime>(
+    app: &AppHandle<R>,
+    ready: &Arc<AtomicBool>,
+    config: &Config,
+) -> Result<Child, String> {
     let current_dir = std::env::current_dir().unwrap_or_default();
     let project_root = if current_dir.ends_with("src-tauri") {
         current_dir.parent().and_then(|p| p.parent()).unwrap_or(&current_dir).to_path_buf()
@@ -16,33 +377,128 @@ fn start_backend<R: Runtime>(_app: &AppHandle<R>) -> Result<Child, String> {
         current_dir.clone()
     };
 
-    // Check if poetry exists
-    let has_poetry = std::process::Command::new("poetry")
-        .arg("--version")
-        .output()
-        .is_ok();
-
-    let (python_cmd, final_args) = if has_poetry {
-        ("poetry", vec!["run".to_string(), "python".to_string(), "-m".to_string(), "voxgrep.server.app".to_string()])
-    } else {
-        let cmd = if cfg!(windows) { "python" } else { "python3" };
-        (cmd, vec!["-m".to_string(), "voxgrep.server.app".to_string()])
-    };
+    let (program, final_args) = resolve_backend_command(&project_root, config).map_err(|err| {
+        // Surface the searched locations to the UI so the failure is actionable.
+        let _ = app.emit("backend-error", err.clone());
+        err.to_string()
+    })?;
 
-    println!("Starting Backend: {} {:?}", python_cmd, final_args);
+    println!("Starting Backend: {:?} {:?}", program, final_args);
     println!("Project Root: {:?}", project_root);
 
-    let child = Command::new(python_cmd)
+    let mut command = Command::new(&program);
+    command
         .args(&final_args)
         .current_dir(&project_root)
+        // Tell the backend which host/port to bind.
+        .env("VOXGREP_HOST", &config.host)
+        .env("VOXGREP_PORT", config.port.to_string())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Run the backend as its own process-group leader so a graceful SIGTERM
+    // can be sent to the whole group. Otherwise, under the default
+    // `poetry run python …` launcher the tracked pid is poetry — which neither
+    // execs nor forwards signals — and the real server would never be told to
+    // shut down cleanly (see `terminate_child`).
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to spawn python backend: {}", e))?;
 
+    spawn_log_pumps(
+        app,
+        child.stdout.take(),
+        child.stderr.take(),
+        ready,
+        &config.ready_marker,
+        &config.host,
+        config.port,
+    );
+
     Ok(child)
 }
 
+/// Stop the backend gracefully: signal it to terminate, wait up to
+/// [`SHUTDOWN_TIMEOUT`] for it to flush indices and release the port, and only
+/// then escalate to a hard `kill()`.
+fn terminate_child(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: `child.id()` is our own child, which leads its own process
+        // group (see `start_backend`). Signalling the group (negative pid)
+        // reaches the real server even when it's wrapped by a `poetry run`
+        // launcher that doesn't forward SIGTERM, giving it a chance to flush
+        // indices, close DB handles, and release the port.
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGTERM);
+        }
+
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while Instant::now() < deadline {
+            match child.try_wait() {
+                Ok(Some(_)) => return,
+                Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+                Err(_) => break,
+            }
+        }
+    }
+
+    // On Windows there is no catchable-signal path, so falling through to the
+    // hard kill immediately is correct — spinning the timeout loop there would
+    // just freeze the app for no graceful benefit. On Unix we only reach here
+    // after the grace period elapsed without a clean exit.
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Gracefully stop the running backend and spawn a fresh one, giving users a
+/// clean "restart server" action.
+#[tauri::command]
+fn restart_backend<R: Runtime>(
+    app: AppHandle<R>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let ready = state.backend_ready.clone();
+    ready.store(false, Ordering::SeqCst);
+    let config = state.config.lock().unwrap().clone();
+
+    // Hold the process lock across stop-and-respawn so the supervisor never
+    // observes the intermediate exit and mistakes it for a crash.
+    let mut lock = state.python_process.lock().unwrap();
+    if let Some(mut child) = lock.take() {
+        terminate_child(&mut child);
+    }
+    let child = start_backend(&app, &ready, &config)?;
+    *lock = Some(child);
+    Ok(())
+}
+
+/// Return the current backend configuration.
+#[tauri::command]
+fn get_config(state: tauri::State<'_, AppState>) -> Config {
+    state.config.lock().unwrap().clone()
+}
+
+/// Persist a new backend configuration. Takes effect on the next
+/// (re)start of the backend.
+#[tauri::command]
+fn set_config<R: Runtime>(
+    app: AppHandle<R>,
+    state: tauri::State<'_, AppState>,
+    config: Config,
+) -> Result<(), String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    config.save(&dir)?;
+    *state.config.lock().unwrap() = config;
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -50,26 +506,66 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .manage(AppState {
             python_process: Arc::new(Mutex::new(None)),
+            manually_killed: Arc::new(AtomicBool::new(false)),
+            backend_ready: Arc::new(AtomicBool::new(false)),
+            config: Arc::new(Mutex::new(Config::default())),
         })
+        .invoke_handler(tauri::generate_handler![
+            wait_for_backend,
+            get_config,
+            set_config,
+            restart_backend
+        ])
         .setup(|app| {
             let handle = app.handle().clone();
             let state = app.state::<AppState>();
-            
-            match start_backend(&handle) {
-                Ok(child) => {
-                    *state.python_process.lock().unwrap() = Some(child);
-                    println!("Backend started successfully.");
+            let ready = state.backend_ready.clone();
+
+            // Load persisted settings before touching the backend.
+            let config = match handle.path().app_config_dir() {
+                Ok(dir) => Config::load(&dir),
+                Err(_) => Config::default(),
+            };
+            *state.config.lock().unwrap() = config.clone();
+
+            if config.auto_start {
+                match start_backend(&handle, &ready, &config) {
+                    Ok(child) => {
+                        *state.python_process.lock().unwrap() = Some(child);
+                        println!("Backend started successfully.");
+                    }
+                    Err(e) => eprintln!("Failed to start backend: {}", e),
                 }
-                Err(e) => eprintln!("Failed to start backend: {}", e),
+
+                spawn_readiness_watch(handle.clone(), ready.clone());
+                spawn_supervisor(
+                    handle,
+                    state.python_process.clone(),
+                    state.manually_killed.clone(),
+                    ready,
+                    state.config.clone(),
+                );
+            } else {
+                // Connect-to-existing mode: assume the configured backend is
+                // already listening and surface it as ready immediately.
+                ready.store(true, Ordering::SeqCst);
+                let _ = handle.emit(
+                    "backend-ready",
+                    BackendReady { host: config.host.clone(), port: config.port },
+                );
             }
             Ok(())
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Destroyed = event {
-                if let Ok(mut lock) = window.state::<AppState>().python_process.lock() {
+                let state = window.state::<AppState>();
+                // Mark the shutdown as intentional before killing so the
+                // supervisor doesn't treat it as a crash and respawn.
+                state.manually_killed.store(true, Ordering::SeqCst);
+                if let Ok(mut lock) = state.python_process.lock() {
                     if let Some(mut child) = lock.take() {
-                        println!("Killing backend process...");
-                        let _ = child.kill();
+                        println!("Shutting down backend process...");
+                        terminate_child(&mut child);
                     }
                 }
             }
@@ -77,3 +573,65 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An explicit, existing `python_path` is used verbatim and runs the
+    /// configured module.
+    #[test]
+    fn explicit_python_path_wins() {
+        // The test binary is a real file, so it stands in for an interpreter
+        // that passes the `is_file()` check.
+        let exe = std::env::current_exe().unwrap();
+        let config = Config {
+            python_path: Some(exe.to_string_lossy().into_owned()),
+            module: "voxgrep.server.app".to_string(),
+            ..Config::default()
+        };
+
+        let (program, args) =
+            resolve_backend_command(Path::new("/nonexistent-project"), &config).unwrap();
+
+        assert_eq!(program, exe);
+        assert_eq!(args, vec!["-m".to_string(), "voxgrep.server.app".to_string()]);
+    }
+
+    /// The launched module tracks `config.module` rather than a hardcoded name.
+    #[test]
+    fn launches_configured_module() {
+        let exe = std::env::current_exe().unwrap();
+        let config = Config {
+            python_path: Some(exe.to_string_lossy().into_owned()),
+            module: "custom.entry".to_string(),
+            ..Config::default()
+        };
+
+        let (_, args) =
+            resolve_backend_command(Path::new("/nonexistent-project"), &config).unwrap();
+
+        assert_eq!(args, vec!["-m".to_string(), "custom.entry".to_string()]);
+    }
+
+    /// A bogus explicit path doesn't short-circuit resolution; it falls through
+    /// to the next candidates, and when resolution fails entirely the error
+    /// names the explicit path it tried first.
+    #[test]
+    fn bogus_explicit_path_falls_through_and_is_recorded() {
+        let bogus = "/no/such/python-interpreter";
+        let config = Config {
+            python_path: Some(bogus.to_string()),
+            ..Config::default()
+        };
+
+        match resolve_backend_command(Path::new("/nonexistent-project"), &config) {
+            // Something else on this host resolved; it must not be the bogus path.
+            Ok((program, _)) => assert_ne!(program, PathBuf::from(bogus)),
+            // Nothing resolved; the searched list leads with the explicit path.
+            Err(err) => {
+                assert_eq!(err.searched.first(), Some(&format!("config.python_path={bogus}")))
+            }
+        }
+    }
+}